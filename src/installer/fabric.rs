@@ -1,7 +1,10 @@
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{format, fs, io::copy, println, vec};
+use std::{format, fs, io::copy, path::Path, println, time::Duration, vec};
+use tokio::sync::mpsc::Sender;
 
+use crate::installer::maven::maven_coordinate_to_path;
 use crate::utils::folder::MinecraftLocation;
 
 #[derive(Debug, Deserialize)]
@@ -32,6 +35,16 @@ pub struct LauncherMeta {
     pub version: usize,
     pub libraries: LauncherMetaLibraries,
     pub mainClass: Value,
+    /// Fabric 1.0+ 的元数据会在这里提供按 `game`/`jvm` 分类的真实启动参数。
+    pub arguments: Option<LauncherMetaArguments>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LauncherMetaArguments {
+    #[serde(default)]
+    pub game: Vec<Value>,
+    #[serde(default)]
+    pub jvm: Vec<Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,67 +60,231 @@ pub struct LauncherMetaLibrariesItems {
     pub url: Option<String>,
 }
 
+/// Fabric 元数据服务的来源配置：可以指向官方 `meta.fabricmc.net`，也可以指向自托管的镜像，
+/// 并在 `cache_dir` 中缓存响应，以便在镜像离线或没有网络时继续提供数据。
+#[derive(Debug, Clone)]
+pub struct FabricMetaSource {
+    /// 元数据服务的 base url，例如 `https://meta.fabricmc.net`。
+    pub base_url: String,
+    /// 缓存响应的磁盘目录。
+    pub cache_dir: std::path::PathBuf,
+    /// 缓存在被判定为过期、需要重新校验之前的最长存活时间。
+    pub max_age: Duration,
+}
+
+impl Default for FabricMetaSource {
+    fn default() -> Self {
+        Self {
+            base_url: String::from("https://meta.fabricmc.net"),
+            cache_dir: std::env::temp_dir().join("fabric-meta-cache"),
+            max_age: Duration::from_secs(3600),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FabricMetaCacheEntry {
+    etag: Option<String>,
+    fetched_at_unix_secs: u64,
+    body: String,
+}
+
+impl FabricMetaSource {
+    fn cache_path(&self, path: &str) -> std::path::PathBuf {
+        let key = path.trim_start_matches('/').replace(['/', ':'], "_");
+        self.cache_dir.join(format!("{}.json", key))
+    }
+
+    async fn read_cache(&self, cache_path: &Path) -> Option<FabricMetaCacheEntry> {
+        let data = tokio::fs::read(cache_path).await.ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    async fn write_cache(&self, cache_path: &Path, entry: &FabricMetaCacheEntry) {
+        if tokio::fs::create_dir_all(&self.cache_dir).await.is_ok() {
+            if let Ok(data) = serde_json::to_vec(entry) {
+                let _ = tokio::fs::write(cache_path, data).await;
+            }
+        }
+    }
+
+    /// 请求 `path` 对应的元数据，优先使用未过期的磁盘缓存；缓存过期时携带 `If-None-Match`
+    /// 重新校验，命中 304 或请求失败时回退到缓存内容。`pub(crate)` 是为了让 Quilt 等其他
+    /// installer 也能复用同一套缓存/镜像逻辑，而不必各自裸调 `reqwest::get`。`path` 也可以是
+    /// 一个完整的 URL（用于请求不在 `base_url` 下、按版本变化的地址），此时不再拼接 `base_url`。
+    ///
+    /// 缓存和镜像都用尽、确实拿不到数据时返回 `Err`，交由调用方决定是直接 panic（现有大多数
+    /// 元数据都是强依赖）还是容忍缺失回退到默认值。
+    pub(crate) async fn fetch_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<T, String> {
+        let cache_path = self.cache_path(path);
+        let cached = self.read_cache(&cache_path).await;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        if let Some(cached) = &cached {
+            if now.saturating_sub(cached.fetched_at_unix_secs) < self.max_age.as_secs() {
+                if let Ok(value) = serde_json::from_str(&cached.body) {
+                    return Ok(value);
+                }
+            }
+        }
+
+        let url = if path.starts_with("http://") || path.starts_with("https://") {
+            path.to_string()
+        } else {
+            format!("{}{}", self.base_url, path)
+        };
+        let mut request = reqwest::Client::new().get(&url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+        }
+
+        match request.send().await {
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_MODIFIED => {
+                let cached = cached.expect("304 response implies a cached entry was sent");
+                let value =
+                    serde_json::from_str(&cached.body).map_err(|error| error.to_string())?;
+                self.write_cache(
+                    &cache_path,
+                    &FabricMetaCacheEntry {
+                        etag: cached.etag,
+                        fetched_at_unix_secs: now,
+                        body: cached.body,
+                    },
+                )
+                .await;
+                Ok(value)
+            }
+            Ok(response) if response.status().is_success() => {
+                let etag = response
+                    .headers()
+                    .get(reqwest::header::ETAG)
+                    .and_then(|value| value.to_str().ok())
+                    .map(String::from);
+                let body = response.text().await.map_err(|error| error.to_string())?;
+                self.write_cache(
+                    &cache_path,
+                    &FabricMetaCacheEntry {
+                        etag,
+                        fetched_at_unix_secs: now,
+                        body: body.clone(),
+                    },
+                )
+                .await;
+                serde_json::from_str(&body).map_err(|error| error.to_string())
+            }
+            Ok(response) => {
+                let status = response.status();
+                match cached {
+                    Some(cached) => {
+                        serde_json::from_str(&cached.body).map_err(|error| error.to_string())
+                    }
+                    None => Err(format!(
+                        "failed to fetch {} ({}) and no cache is available",
+                        url, status
+                    )),
+                }
+            }
+            Err(error) => match cached {
+                Some(cached) => {
+                    serde_json::from_str(&cached.body).map_err(|error| error.to_string())
+                }
+                None => Err(format!(
+                    "failed to fetch {} and no cache is available: {}",
+                    url, error
+                )),
+            },
+        }
+    }
+
+    pub async fn get_fabric_artifacts(&self) -> FabricArtifacts {
+        self.fetch_json("/v2/versions").await.unwrap()
+    }
+
+    pub async fn get_yarn_artifact_list(&self) -> Vec<FabricArtifactVersion> {
+        self.fetch_json("/v2/versions/yarn").await.unwrap()
+    }
+
+    pub async fn get_yarn_artifact_list_for(&self, minecraft: &str) -> Vec<FabricArtifactVersion> {
+        self.fetch_json(&format!("/v2/versions/yarn/{}", minecraft))
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_loader_artifact_list(&self) -> Vec<FabricArtifactVersion> {
+        self.fetch_json("/v2/versions/loader").await.unwrap()
+    }
+
+    pub async fn get_loader_artifact_list_for(&self, minecraft: &str) -> Vec<FabricLoaderArtifact> {
+        self.fetch_json(&format!("/v2/versions/loader/{}", minecraft))
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_fabric_loader_artifact(
+        &self,
+        minecraft: &str,
+        loader: &str,
+    ) -> FabricLoaderArtifact {
+        self.fetch_json(&format!("/v2/versions/loader/{}/{}", minecraft, loader))
+            .await
+            .unwrap()
+    }
+
+    /// Quilt 的元数据服务，与 Fabric 同构但 base url 和缓存目录不同。
+    pub fn quilt() -> Self {
+        Self {
+            base_url: String::from("https://meta.quiltmc.org"),
+            cache_dir: std::env::temp_dir().join("quilt-meta-cache"),
+            max_age: Duration::from_secs(3600),
+        }
+    }
+
+    /// Mojang 官方版本清单，用于查询 `releaseTime`/`time` 等与版本号绑定的元数据。
+    pub fn mojang() -> Self {
+        Self {
+            base_url: String::from("https://piston-meta.mojang.com"),
+            cache_dir: std::env::temp_dir().join("mojang-meta-cache"),
+            max_age: Duration::from_secs(3600),
+        }
+    }
+}
+
 pub async fn get_fabric_artifacts() -> FabricArtifacts {
-    reqwest::get("https://meta.fabricmc.net/v2/versions")
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap()
+    FabricMetaSource::default().get_fabric_artifacts().await
 }
 
 pub async fn get_yarn_artifact_list() -> Vec<FabricArtifactVersion> {
-    reqwest::get("https://meta.fabricmc.net/v2/versions/yarn")
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap()
+    FabricMetaSource::default().get_yarn_artifact_list().await
 }
 
 pub async fn get_yarn_artifact_list_for(minecraft: &str) -> Vec<FabricArtifactVersion> {
-    reqwest::get(format!(
-        "https://meta.fabricmc.net/v2/versions/yarn/{}",
-        minecraft
-    ))
-    .await
-    .unwrap()
-    .json()
-    .await
-    .unwrap()
+    FabricMetaSource::default()
+        .get_yarn_artifact_list_for(minecraft)
+        .await
 }
 
 pub async fn get_loader_artifact_list() -> Vec<FabricArtifactVersion> {
-    reqwest::get("https://meta.fabricmc.net/v2/versions/loader")
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap()
+    FabricMetaSource::default().get_loader_artifact_list().await
 }
 
 pub async fn get_loader_artifact_list_for(minecraft: &str) -> Vec<FabricLoaderArtifact> {
-    reqwest::get(format!(
-        "https://meta.fabricmc.net/v2/versions/loader/{}",
-        minecraft
-    ))
-    .await
-    .unwrap()
-    .json()
-    .await
-    .unwrap()
+    FabricMetaSource::default()
+        .get_loader_artifact_list_for(minecraft)
+        .await
 }
 
 pub async fn get_fabric_loader_artifact(minecraft: &str, loader: &str) -> FabricLoaderArtifact {
-    reqwest::get(format!(
-        "https://meta.fabricmc.net/v2/versions/loader/{}/{}",
-        minecraft, loader
-    ))
-    .await
-    .unwrap()
-    .json()
-    .await
-    .unwrap()
+    FabricMetaSource::default()
+        .get_fabric_loader_artifact(minecraft, loader)
+        .await
 }
 
 pub enum FabricInstallSide {
@@ -119,6 +296,32 @@ pub enum YarnVersion {
     String(String),
     FabricArtifactVersion(FabricArtifactVersion),
 }
+
+/// 安装过程中的进度事件，供前端订阅渲染安装进度。
+#[derive(Debug, Clone)]
+pub enum InstallationUpdate {
+    ResolvingMeta,
+    DownloadingLibrary {
+        name: String,
+        downloaded: u64,
+        total: u64,
+    },
+    LibraryFinished {
+        name: String,
+    },
+    WritingVersionJson,
+    Done,
+}
+
+async fn send_update(
+    update_sender: &Option<Sender<InstallationUpdate>>,
+    update: InstallationUpdate,
+) {
+    if let Some(update_sender) = update_sender {
+        let _ = update_sender.send(update).await;
+    }
+}
+
 pub struct FabricInstallOptions {
     /// 当你想要在另一个版本的基础上安装一个版本时。
     pub inherits_from: Option<String>,
@@ -127,20 +330,259 @@ pub struct FabricInstallOptions {
     pub version_id: Option<String>,
     pub size: Option<FabricInstallSide>,
     pub yarn_version: Option<YarnVersion>,
+
+    /// 在写入版本 JSON 之后，顺带把解析出来的库下载到本地。
+    pub download_libraries: Option<FabricLibraryDownloadOptions>,
+
+    /// 查询 `releaseTime`/`time` 时使用的 Mojang 元数据来源，默认 [`FabricMetaSource::mojang`]。
+    /// 可以指向自托管的镜像以支持离线/可缓存安装。
+    pub mojang_meta_source: Option<FabricMetaSource>,
 }
 
-/// 根据 yarn 和 loader 生成 fabric 版本的 JSON 文件到磁盘中。
-pub async fn install_fabric(
+/// 控制 [`install_fabric_libraries`] 下载库文件时的并发度、重试次数和校验行为。
+#[derive(Debug, Clone, Copy)]
+pub struct FabricLibraryDownloadOptions {
+    /// 同时进行下载的任务数量。
+    pub parallel: u16,
+    /// 单个库下载失败后的重试次数。
+    pub retries: u16,
+    /// 是否使用 maven 仓库旁的 `.jar.sha1` 校验下载结果，校验失败会重新下载。
+    pub verify: bool,
+}
+
+impl Default for FabricLibraryDownloadOptions {
+    fn default() -> Self {
+        Self {
+            parallel: 4,
+            retries: 3,
+            verify: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MojangVersionManifest {
+    versions: Vec<MojangVersionManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MojangVersionManifestEntry {
+    id: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MojangVersionMeta {
+    releaseTime: String,
+    time: String,
+}
+
+/// 从 Mojang 的版本清单中查出 `minecraft_version` 对应的 `releaseTime`/`time`，
+/// 查不到时回退到一个固定的时间戳而不是报错，因为这两个字段对启动流程不是强依赖。
+///
+/// `source` 复用 [`FabricMetaSource`] 的磁盘缓存机制，因此一旦缓存预热过，这两个值也能在
+/// 完全离线的情况下解析出来，而不必每次安装都依赖 Mojang 的实时接口。
+pub(crate) async fn resolve_minecraft_version_times(
+    source: &FabricMetaSource,
+    minecraft_version: &str,
+) -> (String, String) {
+    let fallback = (
+        String::from("1970-01-01T00:00:00+00:00"),
+        String::from("1970-01-01T00:00:00+00:00"),
+    );
+
+    let manifest: MojangVersionManifest =
+        match source.fetch_json("/mc/game/version_manifest_v2.json").await {
+            Ok(manifest) => manifest,
+            Err(_) => return fallback,
+        };
+    let entry = manifest
+        .versions
+        .into_iter()
+        .find(|version| version.id == minecraft_version);
+    let entry = match entry {
+        Some(entry) => entry,
+        None => return fallback,
+    };
+
+    match source.fetch_json::<MojangVersionMeta>(&entry.url).await {
+        Ok(meta) => (meta.releaseTime, meta.time),
+        Err(_) => fallback,
+    }
+}
+
+async fn fetch_expected_sha1(jar_url: &str) -> Option<String> {
+    let response = reqwest::get(format!("{}.sha1", jar_url)).await.ok()?;
+    let text = response.text().await.ok()?;
+    Some(text.trim().to_string())
+}
+
+fn sha1_hex(data: &[u8]) -> String {
+    use sha1::{Digest, Sha1};
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+async fn download_and_verify(
+    name: &str,
+    url: &str,
+    path: &Path,
+    expected_sha1: Option<&str>,
+    update_sender: &Option<Sender<InstallationUpdate>>,
+) -> Result<(), ()> {
+    let response = reqwest::get(url).await.map_err(|_| ())?;
+    let total = response.content_length().unwrap_or(0);
+    let mut downloaded = 0u64;
+    let mut bytes = Vec::with_capacity(total as usize);
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|_| ())?;
+        downloaded += chunk.len() as u64;
+        bytes.extend_from_slice(&chunk);
+        send_update(
+            update_sender,
+            InstallationUpdate::DownloadingLibrary {
+                name: name.to_string(),
+                downloaded,
+                total,
+            },
+        )
+        .await;
+    }
+    if let Some(expected) = expected_sha1 {
+        if sha1_hex(&bytes) != expected {
+            return Err(());
+        }
+    }
+    tokio::fs::write(path, &bytes).await.map_err(|_| ())?;
+    Ok(())
+}
+
+async fn download_fabric_library(
+    library: LauncherMetaLibrariesItems,
+    minecraft_location: MinecraftLocation,
+    options: FabricLibraryDownloadOptions,
+    update_sender: Option<Sender<InstallationUpdate>>,
+) {
+    let name = match &library.name {
+        Some(name) => name.clone(),
+        None => return,
+    };
+    let relative_path = maven_coordinate_to_path(&name);
+    let base_url = library
+        .url
+        .clone()
+        .unwrap_or_else(|| String::from("https://maven.fabricmc.net/"));
+    let jar_url = format!("{}{}", base_url, relative_path);
+    let jar_path = minecraft_location.get_library_by_path(&relative_path);
+    fs::create_dir_all(jar_path.parent().unwrap()).unwrap();
+
+    let expected_sha1 = if options.verify {
+        fetch_expected_sha1(&jar_url).await
+    } else {
+        None
+    };
+
+    let mut attempt = 0u16;
+    loop {
+        if download_and_verify(
+            &name,
+            &jar_url,
+            &jar_path,
+            expected_sha1.as_deref(),
+            &update_sender,
+        )
+        .await
+        .is_ok()
+        {
+            send_update(&update_sender, InstallationUpdate::LibraryFinished { name }).await;
+            return;
+        }
+        attempt += 1;
+        if attempt > options.retries {
+            panic!(
+                "failed to download library {} after {} attempts",
+                name, attempt
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+    }
+}
+
+/// 下载 `libraries` 中解析出的所有库文件到 `minecraft_location` 的 libraries 目录。
+pub async fn install_fabric_libraries(
+    libraries: &[LauncherMetaLibrariesItems],
+    minecraft_location: &MinecraftLocation,
+    options: FabricLibraryDownloadOptions,
+    update_sender: Option<Sender<InstallationUpdate>>,
+) {
+    stream::iter(libraries.iter().cloned().map(|library| {
+        let minecraft_location = minecraft_location.clone();
+        let update_sender = update_sender.clone();
+        async move {
+            download_fabric_library(library, minecraft_location, options, update_sender).await;
+        }
+    }))
+    .buffer_unordered(options.parallel.max(1) as usize)
+    .collect::<Vec<_>>()
+    .await;
+}
+
+/// 描述 Fabric 与 Quilt 之间的差异，使两者可以共用同一套 [`install_loader`] 实现：
+/// maven 仓库地址不同，mappings 的 maven 坐标(`net.fabricmc:yarn`/`org.quiltmc:quilt-mappings`)不同，
+/// 版本 id 的后缀也不同。loader 与 intermediary 的坐标直接取自元数据，不在这里描述。
+pub(crate) struct LoaderVariant {
+    pub maven_base: &'static str,
+    /// 根据 mappings 版本号构造完整的 maven 坐标，例如 `net.fabricmc:yarn:{version}`。
+    pub mapping_coordinate: fn(&str) -> String,
+    /// 指定了 mappings 时使用的版本 id 后缀，例如 `loader`/`quilt-mappings`。
+    pub mapped_id_suffix: &'static str,
+    /// 未指定 mappings 时使用的版本 id 后缀，例如 `fabric`/`quilt`。
+    pub unmapped_id_suffix: &'static str,
+}
+
+impl LoaderVariant {
+    pub fn fabric() -> Self {
+        Self {
+            maven_base: "https://maven.fabricmc.net/",
+            mapping_coordinate: |version| format!("net.fabricmc:yarn:{}", version),
+            mapped_id_suffix: "loader",
+            unmapped_id_suffix: "fabric",
+        }
+    }
+
+    pub fn quilt() -> Self {
+        Self {
+            maven_base: "https://maven.quiltmc.org/repository/release/",
+            mapping_coordinate: |version| format!("org.quiltmc:quilt-mappings:{}", version),
+            mapped_id_suffix: "quilt-mappings",
+            unmapped_id_suffix: "quilt",
+        }
+    }
+}
+
+/// 根据 yarn(或等价的 mappings)和 loader 生成版本 JSON 文件到磁盘中，Fabric 与 Quilt 共用此实现，
+/// 仅通过 `variant` 区分 maven 仓库地址和命名规则。
+pub(crate) async fn install_loader(
+    variant: LoaderVariant,
     loader: FabricLoaderArtifact,
     minecraft_location: MinecraftLocation,
-    options: FabricInstallOptions,
+    inherits_from: Option<String>,
+    version_id: Option<String>,
+    side: Option<FabricInstallSide>,
+    yarn_version: Option<YarnVersion>,
+    download_libraries: Option<FabricLibraryDownloadOptions>,
+    mojang_meta_source: Option<FabricMetaSource>,
+    update_sender: Option<Sender<InstallationUpdate>>,
 ) -> String {
+    send_update(&update_sender, InstallationUpdate::ResolvingMeta).await;
     let yarn: Option<String>;
-    let side = options.size.unwrap_or(FabricInstallSide::Client);
-    let mut id = options.version_id;
+    let side = side.unwrap_or(FabricInstallSide::Client);
+    let mut id = version_id;
     let mut minecraft_version = "".to_string();
 
-    match options.yarn_version {
+    match yarn_version {
         Some(yarn_version) => match yarn_version {
             YarnVersion::String(yarn_version) => {
                 yarn = Some(yarn_version);
@@ -155,32 +597,32 @@ pub async fn install_fabric(
         }
     }
     if let None = id {
-        if let Some(yarn) = yarn.clone() {
+        if yarn.is_some() {
             id = Some(format!(
-                "{}-loader{}",
-                minecraft_version, loader.loader.version
+                "{}-{}{}",
+                minecraft_version, variant.mapped_id_suffix, loader.loader.version
             ));
         } else {
             id = Some(format!(
-                "{}-fabric{}",
-                minecraft_version, loader.loader.version
+                "{}-{}{}",
+                minecraft_version, variant.unmapped_id_suffix, loader.loader.version
             ))
         }
     }
     let mut libraries = vec![
         LauncherMetaLibrariesItems {
             name: Some(loader.loader.maven.clone()),
-            url: Some(String::from("https://maven.fabricmc.net/")),
+            url: Some(String::from(variant.maven_base)),
         },
         LauncherMetaLibrariesItems {
             name: Some(loader.intermediary.maven.clone()),
-            url: Some(String::from("https://maven.fabricmc.net/")),
+            url: Some(String::from(variant.maven_base)),
         },
     ];
     if let Some(yarn) = yarn.clone() {
         libraries.push(LauncherMetaLibrariesItems {
-            name: Some(format!("net.fabricmc:yarn:{}", yarn)),
-            url: Some(String::from("https://maven.fabricmc.net/")),
+            name: Some((variant.mapping_coordinate)(&yarn)),
+            url: Some(String::from(variant.maven_base)),
         });
     }
     libraries.extend(loader.launcherMeta.libraries.common.iter().cloned());
@@ -202,7 +644,7 @@ pub async fn install_fabric(
             .unwrap_or(loader.launcherMeta.mainClass.as_str().unwrap_or(""))
             .to_string(),
     };
-    let inherits_from = options.inherits_from.unwrap_or(minecraft_version);
+    let inherits_from = inherits_from.unwrap_or(minecraft_version);
 
     let json_file_path = minecraft_location.get_version_json(&id.clone().unwrap());
     fs::create_dir_all(json_file_path.parent().unwrap()).unwrap();
@@ -214,51 +656,130 @@ pub async fn install_fabric(
         }
     }
     #[derive(Serialize)]
-    struct FabricVersionJSON {
+    struct LoaderVersionJSON {
         id: String,
         inheritsFrom: String,
         mainClass: String,
-        libraries: String,
-        arguments: FabricVersionJSONArg,
+        libraries: Vec<LauncherMetaLibrariesItems>,
+        arguments: LoaderVersionJSONArg,
         releaseTime: String,
         time: String,
     }
     #[derive(Serialize)]
-    struct FabricVersionJSONArg {
-        game: Vec<i32>,
-        jvm: Vec<i32>,
+    struct LoaderVersionJSONArg {
+        game: Vec<Value>,
+        jvm: Vec<Value>,
     }
-    let version_json = FabricVersionJSON {
+    let mojang_meta_source = mojang_meta_source.unwrap_or_else(FabricMetaSource::mojang);
+    let (release_time, time) =
+        resolve_minecraft_version_times(&mojang_meta_source, &inherits_from).await;
+    let arguments = loader
+        .launcherMeta
+        .arguments
+        .unwrap_or(LauncherMetaArguments {
+            game: vec![],
+            jvm: vec![],
+        });
+    let version_json = LoaderVersionJSON {
         id: id.clone().unwrap_or("".to_string()),
         inheritsFrom: inherits_from,
         mainClass: main_class,
-        libraries: serde_json::to_string(&libraries).unwrap_or("".to_string()),
-        arguments: FabricVersionJSONArg {
-            game: vec![],
-            jvm: vec![],
+        libraries,
+        arguments: LoaderVersionJSONArg {
+            game: arguments.game,
+            jvm: arguments.jvm,
         },
-        releaseTime: "2023-05-13T15:58:54.493Z".to_string(),
-        time: "2023-05-13T15:58:54.493Z".to_string(),
+        releaseTime: release_time,
+        time,
     };
     let json_data = serde_json::to_string_pretty(&version_json)
         .unwrap_or("".to_string())
         .to_string();
+    send_update(&update_sender, InstallationUpdate::WritingVersionJson).await;
     tokio::fs::write(json_file_path, json_data).await.unwrap();
 
+    if let Some(download_options) = download_libraries {
+        install_fabric_libraries(
+            &version_json.libraries,
+            &minecraft_location,
+            download_options,
+            update_sender.clone(),
+        )
+        .await;
+    }
+
+    send_update(&update_sender, InstallationUpdate::Done).await;
+
     id.unwrap_or("".to_string())
 }
 
+/// 根据 yarn 和 loader 生成 fabric 版本的 JSON 文件到磁盘中。
+pub async fn install_fabric(
+    loader: FabricLoaderArtifact,
+    minecraft_location: MinecraftLocation,
+    options: FabricInstallOptions,
+    update_sender: Option<Sender<InstallationUpdate>>,
+) -> String {
+    install_loader(
+        LoaderVariant::fabric(),
+        loader,
+        minecraft_location,
+        options.inherits_from,
+        options.version_id,
+        options.size,
+        options.yarn_version,
+        options.download_libraries,
+        options.mojang_meta_source,
+        update_sender,
+    )
+    .await
+}
+
 #[tokio::test]
 async fn test() {
     // let b = get_loader_artifact_list().await;
     let a = get_fabric_loader_artifact("1.19.4", "0.1.0.48").await;
+    let loader_maven = a.loader.maven.clone();
     let options = FabricInstallOptions {
         inherits_from: None,
         version_id: None,
         size: None,
-        yarn_version: None
+        yarn_version: None,
+        download_libraries: Some(FabricLibraryDownloadOptions::default()),
+        mojang_meta_source: None,
     };
     let location = MinecraftLocation::new("test");
+    let (update_sender, mut update_receiver) = tokio::sync::mpsc::channel(32);
+    // 进度事件必须在安装进行的同时消费：channel 是有界的，写满后 `send_update` 会一直等待。
+    let collector = tokio::spawn(async move {
+        let mut saw_downloading = false;
+        let mut saw_library_finished = false;
+        let mut saw_done = false;
+        while let Some(update) = update_receiver.recv().await {
+            match update {
+                InstallationUpdate::DownloadingLibrary { .. } => saw_downloading = true,
+                InstallationUpdate::LibraryFinished { .. } => saw_library_finished = true,
+                InstallationUpdate::Done => saw_done = true,
+                _ => {}
+            }
+        }
+        (saw_downloading, saw_library_finished, saw_done)
+    });
     // println!("{:#?}",a);
-        install_fabric(a, location, options).await;
+    let id = install_fabric(a, location.clone(), options, Some(update_sender)).await;
+
+    let (saw_downloading, saw_library_finished, saw_done) = collector.await.unwrap();
+    assert!(
+        saw_downloading,
+        "expected at least one DownloadingLibrary update"
+    );
+    assert!(
+        saw_library_finished,
+        "expected at least one LibraryFinished update"
+    );
+    assert!(saw_done, "expected a Done update");
+    assert!(location.get_version_json(&id).exists());
+    assert!(location
+        .get_library_by_path(&maven_coordinate_to_path(&loader_maven))
+        .exists());
 }
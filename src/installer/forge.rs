@@ -0,0 +1,486 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, format, fs, io::Read, path::PathBuf, process::Command, vec};
+
+use tokio::sync::mpsc::Sender;
+
+use crate::installer::fabric::InstallationUpdate;
+use crate::installer::maven::maven_coordinate_to_path;
+use crate::utils::folder::MinecraftLocation;
+
+/// 区分 Forge 与 NeoForge：两者共用同一套 installer/processor 机制，
+/// 只是 maven 坐标、仓库地址不同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeFlavor {
+    Forge,
+    NeoForge,
+}
+
+impl ForgeFlavor {
+    fn maven_group(&self) -> &'static str {
+        match self {
+            ForgeFlavor::Forge => "net.minecraftforge",
+            ForgeFlavor::NeoForge => "net.neoforged",
+        }
+    }
+
+    fn artifact_id(&self) -> &'static str {
+        match self {
+            ForgeFlavor::Forge => "forge",
+            ForgeFlavor::NeoForge => "neoforge",
+        }
+    }
+
+    fn maven_base(&self) -> &'static str {
+        match self {
+            ForgeFlavor::Forge => "https://maven.minecraftforge.net/",
+            ForgeFlavor::NeoForge => "https://maven.neoforged.net/releases/",
+        }
+    }
+
+    /// promotion 清单地址，用于解析 `recommended`/`latest` 版本号。
+    fn promotions_url(&self) -> String {
+        match self {
+            ForgeFlavor::Forge => String::from(
+                "https://maven.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json",
+            ),
+            ForgeFlavor::NeoForge => String::from(
+                "https://maven.neoforged.net/releases/net/neoforged/neoforge/maven-metadata.json",
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ForgePromotions {
+    promos: HashMap<String, String>,
+}
+
+/// 获取某个 Minecraft 版本下 Forge 推荐的（`recommended`）和最新的（`latest`）版本号。
+/// NeoForge 没有这种分类：返回的每一项都以版本号本身作为键和值。
+pub async fn get_forge_version_list(
+    flavor: ForgeFlavor,
+    minecraft: &str,
+) -> HashMap<String, String> {
+    match flavor {
+        ForgeFlavor::Forge => {
+            let promotions: ForgePromotions = reqwest::get(flavor.promotions_url())
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            promotions
+                .promos
+                .into_iter()
+                .filter_map(|(key, version)| {
+                    key.strip_prefix(&format!("{}-", minecraft))
+                        .map(|channel| (channel.to_string(), version))
+                })
+                .collect()
+        }
+        ForgeFlavor::NeoForge => {
+            // NeoForge 的 maven-metadata 不按 Minecraft 版本分组，这里只保留以 `minecraft` 开头的版本号。
+            let metadata: Value = reqwest::get(flavor.promotions_url())
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            let versions = metadata["versions"].as_array().cloned().unwrap_or_default();
+            versions
+                .into_iter()
+                .filter_map(|version| version.as_str().map(String::from))
+                .filter(|version| version.starts_with(minecraft))
+                .map(|version| (version.clone(), version))
+                .collect()
+        }
+    }
+}
+
+/// `install_profile.json` 中描述的一个后处理步骤：下载、合并、打补丁等均以运行一个 jar 的
+/// 形式完成，`args` 中可能包含 `[coordinate]` 占位符，需要被替换为 libraries/data 解析出的实际路径。
+#[derive(Debug, Deserialize)]
+pub struct ForgeProcessor {
+    pub jar: String,
+    #[serde(default)]
+    pub classpath: Vec<String>,
+    pub args: Vec<String>,
+    pub outputs: Option<HashMap<String, String>>,
+    pub sides: Option<Vec<String>>,
+}
+
+/// `data` 段里的一项，按客户端/服务端分别给出取值（可能是字面量，也可能是 `[coordinate]` 占位符）。
+#[derive(Debug, Deserialize)]
+pub struct ForgeSidedData {
+    pub client: String,
+    pub server: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgeLibraryArtifact {
+    /// 该库在仓库中的相对路径，缺省时从 `name` 推导。
+    pub path: Option<String>,
+    /// 下载地址；为空字符串代表这个 jar 并不在任何 maven 仓库上，而是直接打包在安装器 jar 的
+    /// `maven/` 目录下，需要从安装器里解出来。
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgeLibraryDownloads {
+    pub artifact: ForgeLibraryArtifact,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgeLibrary {
+    pub name: String,
+    pub downloads: Option<ForgeLibraryDownloads>,
+}
+
+/// installer jar 内 `install_profile.json` 的结构。
+#[derive(Debug, Deserialize)]
+pub struct ForgeInstallProfile {
+    pub spec: Option<usize>,
+    pub minecraft: String,
+    pub version: String,
+    pub json: String,
+    pub libraries: Vec<ForgeLibrary>,
+    #[serde(default)]
+    pub data: HashMap<String, ForgeSidedData>,
+    #[serde(default)]
+    pub processors: Vec<ForgeProcessor>,
+}
+
+pub enum ForgeInstallSide {
+    Client,
+    Server,
+}
+
+pub struct ForgeInstallOptions {
+    /// 覆盖新安装的版本 id，默认为 `{minecraft_version}-{forge|neoforge}{forge_version}`。
+    pub version_id: Option<String>,
+    pub side: Option<ForgeInstallSide>,
+    /// 运行 processor 所使用的 java 可执行文件路径，默认 `java`。
+    pub java_path: Option<String>,
+}
+
+/// 下载（或从安装器 jar 中解出）一个库文件。`downloads.artifact.url` 为空字符串时，
+/// 说明这个 jar 并不挂在任何 maven 仓库上，而是打包进了安装器 jar 的 `maven/<path>` 里。
+async fn download_forge_library(
+    library: &ForgeLibrary,
+    base_url: &str,
+    installer_path: &std::path::Path,
+    minecraft_location: &MinecraftLocation,
+) -> PathBuf {
+    let artifact = library
+        .downloads
+        .as_ref()
+        .map(|downloads| &downloads.artifact);
+    let relative_path = artifact
+        .and_then(|artifact| artifact.path.clone())
+        .unwrap_or_else(|| maven_coordinate_to_path(&library.name));
+    let path = minecraft_location.get_library_by_path(&relative_path);
+    if path.exists() {
+        return path;
+    }
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    // `downloads` 缺省时该库按普通 maven 依赖处理；`downloads.artifact.url` 存在但为空字符串，
+    // 说明这个 jar 没有挂在任何 maven 仓库上，而是直接打包进了安装器 jar。
+    match artifact {
+        Some(artifact) if artifact.url.is_empty() => {
+            let bytes = extract_file_from_jar(installer_path, &format!("maven/{}", relative_path));
+            tokio::fs::write(&path, bytes).await.unwrap();
+        }
+        Some(artifact) => {
+            let bytes = reqwest::get(&artifact.url)
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap();
+            tokio::fs::write(&path, &bytes).await.unwrap();
+        }
+        None => {
+            let url = format!("{}{}", base_url, relative_path);
+            let bytes = reqwest::get(&url).await.unwrap().bytes().await.unwrap();
+            tokio::fs::write(&path, &bytes).await.unwrap();
+        }
+    }
+    path
+}
+
+/// 解析 `install_profile.json` `data` 段里的一个取值：
+/// - `[net.minecraftforge:forge:1.20.1-47.2.0]` 这样的方括号是 maven 坐标，指向磁盘上的库文件；
+/// - `/data/client.lzma` 这样以 `/` 开头的是安装器 jar 内的条目，需要解出到磁盘后换成实际路径；
+/// - 其余情况视为字面量，原样返回。
+fn resolve_data_value(
+    value: &str,
+    minecraft_location: &MinecraftLocation,
+    installer_path: &std::path::Path,
+) -> String {
+    if let Some(coordinate) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        let relative_path = maven_coordinate_to_path(coordinate);
+        minecraft_location
+            .get_library_by_path(&relative_path)
+            .to_string_lossy()
+            .to_string()
+    } else if let Some(entry_name) = value.strip_prefix('/') {
+        let bytes = extract_file_from_jar(installer_path, entry_name);
+        let extracted_path = minecraft_location
+            .root
+            .join("forge-installers")
+            .join("extracted")
+            .join(entry_name.replace('/', "_"));
+        fs::create_dir_all(extracted_path.parent().unwrap()).unwrap();
+        fs::write(&extracted_path, bytes).unwrap();
+        extracted_path.to_string_lossy().to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn extract_file_from_jar(jar_path: &std::path::Path, entry_name: &str) -> Vec<u8> {
+    let file = fs::File::open(jar_path).unwrap();
+    let mut archive = zip::ZipArchive::new(file).unwrap();
+    let mut entry = archive.by_name(entry_name).unwrap();
+    let mut buffer = Vec::new();
+    entry.read_to_end(&mut buffer).unwrap();
+    buffer
+}
+
+/// 依次运行 `install_profile.json` 中声明的 processors，每一步都是启动安装器自带的一个 jar，
+/// 对客户端 jar 打补丁、生成 srg 映射等。`side` 为 `server` 时跳过标记了 `sides: ["client"]` 的步骤，反之亦然。
+///
+/// 除了 `data` 段里声明的键以外，每个 processor 还能引用几个安装器隐式提供的计算值：
+/// `{SIDE}`、`{ROOT}`、`{INSTALLER}`、`{MINECRAFT_JAR}`。
+fn run_forge_processors(
+    profile: &ForgeInstallProfile,
+    side: &ForgeInstallSide,
+    minecraft_location: &MinecraftLocation,
+    installer_path: &std::path::Path,
+    minecraft_jar_path: &std::path::Path,
+    java_path: &str,
+) {
+    let side_name = match side {
+        ForgeInstallSide::Client => "client",
+        ForgeInstallSide::Server => "server",
+    };
+    let mut data: HashMap<String, String> = profile
+        .data
+        .iter()
+        .map(|(key, value)| {
+            let raw = match side {
+                ForgeInstallSide::Client => &value.client,
+                ForgeInstallSide::Server => &value.server,
+            };
+            (
+                key.clone(),
+                resolve_data_value(raw, minecraft_location, installer_path),
+            )
+        })
+        .collect();
+    data.insert(String::from("SIDE"), side_name.to_string());
+    data.insert(
+        String::from("ROOT"),
+        minecraft_location.root.to_string_lossy().to_string(),
+    );
+    data.insert(
+        String::from("INSTALLER"),
+        installer_path.to_string_lossy().to_string(),
+    );
+    data.insert(
+        String::from("MINECRAFT_JAR"),
+        minecraft_jar_path.to_string_lossy().to_string(),
+    );
+
+    for processor in &profile.processors {
+        if let Some(sides) = &processor.sides {
+            if !sides.iter().any(|s| s == side_name) {
+                continue;
+            }
+        }
+        let jar_path =
+            minecraft_location.get_library_by_path(&maven_coordinate_to_path(&processor.jar));
+        let classpath: Vec<String> = processor
+            .classpath
+            .iter()
+            .map(|entry| {
+                minecraft_location
+                    .get_library_by_path(&maven_coordinate_to_path(entry))
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .chain(std::iter::once(jar_path.to_string_lossy().to_string()))
+            .collect();
+        let main_class = read_jar_main_class(&jar_path);
+        let args: Vec<String> = processor
+            .args
+            .iter()
+            .map(|arg| {
+                if let Some(key) = arg.strip_prefix('{').and_then(|a| a.strip_suffix('}')) {
+                    data.get(key).cloned().unwrap_or_else(|| {
+                        panic!(
+                            "forge processor {} references unknown data key {{{}}}",
+                            processor.jar, key
+                        )
+                    })
+                } else {
+                    resolve_data_value(arg, minecraft_location, installer_path)
+                }
+            })
+            .collect();
+
+        let status = Command::new(java_path)
+            .arg("-cp")
+            .arg(classpath.join(if cfg!(windows) { ";" } else { ":" }))
+            .arg(main_class)
+            .args(args)
+            .status()
+            .unwrap();
+        if !status.success() {
+            panic!("forge processor {} exited with {}", processor.jar, status);
+        }
+    }
+}
+
+fn read_jar_main_class(jar_path: &std::path::Path) -> String {
+    let manifest = extract_file_from_jar(jar_path, "META-INF/MANIFEST.MF");
+    let manifest = String::from_utf8_lossy(&manifest);
+    manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("Main-Class: "))
+        .expect("processor jar is missing a Main-Class manifest entry")
+        .trim()
+        .to_string()
+}
+
+/// 下载安装器 jar，解析出 `install_profile.json` 与内嵌的 `version.json`，下载所有 libraries，
+/// 依次运行 processors 完成打补丁/生成映射，最后把合并出的版本 JSON 写入 `minecraft_location`，
+/// `inheritsFrom` 指向对应的原版 Minecraft 版本。
+pub async fn install_forge(
+    flavor: ForgeFlavor,
+    minecraft_version: &str,
+    forge_version: &str,
+    minecraft_location: MinecraftLocation,
+    options: ForgeInstallOptions,
+    update_sender: Option<Sender<InstallationUpdate>>,
+) -> String {
+    if let Some(sender) = &update_sender {
+        let _ = sender.send(InstallationUpdate::ResolvingMeta).await;
+    }
+
+    let full_version = format!("{}-{}", minecraft_version, forge_version);
+    let installer_coordinate = format!(
+        "{}:{}:{}:installer",
+        flavor.maven_group(),
+        flavor.artifact_id(),
+        full_version
+    );
+    let installer_url = format!(
+        "{}{}",
+        flavor.maven_base(),
+        maven_coordinate_to_path(&installer_coordinate)
+    );
+    let installer_path = minecraft_location
+        .root
+        .join("forge-installers")
+        .join(format!("{}-installer.jar", full_version));
+    fs::create_dir_all(installer_path.parent().unwrap()).unwrap();
+    let installer_bytes = reqwest::get(&installer_url)
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    tokio::fs::write(&installer_path, &installer_bytes)
+        .await
+        .unwrap();
+
+    let profile_json = extract_file_from_jar(&installer_path, "install_profile.json");
+    let profile: ForgeInstallProfile = serde_json::from_slice(&profile_json).unwrap();
+    let version_json_bytes =
+        extract_file_from_jar(&installer_path, &profile.json.trim_start_matches('/'));
+    let mut version_json: Value = serde_json::from_slice(&version_json_bytes).unwrap();
+
+    for library in &profile.libraries {
+        download_forge_library(
+            library,
+            flavor.maven_base(),
+            &installer_path,
+            &minecraft_location,
+        )
+        .await;
+        if let Some(sender) = &update_sender {
+            let _ = sender
+                .send(InstallationUpdate::LibraryFinished {
+                    name: library.name.clone(),
+                })
+                .await;
+        }
+    }
+
+    let side = options.side.unwrap_or(ForgeInstallSide::Client);
+    let java_path = options.java_path.unwrap_or_else(|| String::from("java"));
+    let minecraft_jar_path = minecraft_location
+        .get_version_json(minecraft_version)
+        .with_extension("jar");
+    run_forge_processors(
+        &profile,
+        &side,
+        &minecraft_location,
+        &installer_path,
+        &minecraft_jar_path,
+        &java_path,
+    );
+
+    let id = options.version_id.unwrap_or_else(|| {
+        format!(
+            "{}-{}{}",
+            minecraft_version,
+            flavor.artifact_id(),
+            forge_version
+        )
+    });
+    version_json["id"] = Value::String(id.clone());
+    version_json["inheritsFrom"] = Value::String(minecraft_version.to_string());
+
+    if let Some(sender) = &update_sender {
+        let _ = sender.send(InstallationUpdate::WritingVersionJson).await;
+    }
+    let json_file_path = minecraft_location.get_version_json(&id);
+    fs::create_dir_all(json_file_path.parent().unwrap()).unwrap();
+    tokio::fs::write(
+        &json_file_path,
+        serde_json::to_string_pretty(&version_json).unwrap(),
+    )
+    .await
+    .unwrap();
+
+    if let Some(sender) = &update_sender {
+        let _ = sender.send(InstallationUpdate::Done).await;
+    }
+
+    id
+}
+
+#[tokio::test]
+async fn test() {
+    let versions = get_forge_version_list(ForgeFlavor::Forge, "1.20.1").await;
+    let forge_version = versions.get("recommended").unwrap().clone();
+    let options = ForgeInstallOptions {
+        version_id: None,
+        side: None,
+        java_path: None,
+    };
+    let location = MinecraftLocation::new("test");
+    install_forge(
+        ForgeFlavor::Forge,
+        "1.20.1",
+        &forge_version,
+        location,
+        options,
+        None,
+    )
+    .await;
+}
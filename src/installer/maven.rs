@@ -0,0 +1,13 @@
+/// 将 maven 坐标 `group:artifact:version[:classifier]` 转换为仓库相对路径，
+/// 供 fabric/quilt/forge 等 installer 共用。
+pub(crate) fn maven_coordinate_to_path(coordinate: &str) -> String {
+    let parts: Vec<&str> = coordinate.split(':').collect();
+    let group = parts[0].replace('.', "/");
+    let artifact = parts[1];
+    let version = parts[2];
+    let file_name = match parts.get(3) {
+        Some(classifier) => format!("{}-{}-{}.jar", artifact, version, classifier),
+        None => format!("{}-{}.jar", artifact, version),
+    };
+    format!("{}/{}/{}/{}", group, artifact, version, file_name)
+}
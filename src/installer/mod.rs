@@ -0,0 +1,4 @@
+pub mod fabric;
+pub mod forge;
+mod maven;
+pub mod quilt;
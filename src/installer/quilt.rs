@@ -0,0 +1,80 @@
+use std::format;
+
+use tokio::sync::mpsc::Sender;
+
+use crate::installer::fabric::{
+    install_loader, FabricInstallSide, FabricLibraryDownloadOptions, FabricLoaderArtifact,
+    FabricMetaSource, InstallationUpdate, LoaderVariant, YarnVersion,
+};
+use crate::utils::folder::MinecraftLocation;
+
+pub struct QuiltInstallOptions {
+    /// 当你想要在另一个版本的基础上安装一个版本时。
+    pub inherits_from: Option<String>,
+
+    /// 覆盖新安装的版本 id。
+    pub version_id: Option<String>,
+    pub size: Option<FabricInstallSide>,
+    pub yarn_version: Option<YarnVersion>,
+
+    /// 在写入版本 JSON 之后，顺带把解析出来的库下载到本地。
+    pub download_libraries: Option<FabricLibraryDownloadOptions>,
+
+    /// 查询 `releaseTime`/`time` 时使用的 Mojang 元数据来源，默认 [`FabricMetaSource::mojang`]。
+    pub mojang_meta_source: Option<FabricMetaSource>,
+}
+
+/// 查询某个 Minecraft 版本下所有可用的 Quilt loader，经由 [`FabricMetaSource::quilt`] 缓存，
+/// 与 Fabric 元数据共用同一套磁盘缓存逻辑。这里固定请求 `https://meta.quiltmc.org`，不能改指向镜像。
+pub async fn get_quilt_loader_artifact_list_for(minecraft: &str) -> Vec<FabricLoaderArtifact> {
+    FabricMetaSource::quilt()
+        .fetch_json(&format!("/v3/versions/loader/{}", minecraft))
+        .await
+        .unwrap()
+}
+
+pub async fn get_quilt_loader_artifact(minecraft: &str, loader: &str) -> FabricLoaderArtifact {
+    FabricMetaSource::quilt()
+        .fetch_json(&format!("/v3/versions/loader/{}/{}", minecraft, loader))
+        .await
+        .unwrap()
+}
+
+/// 根据 yarn(或 QuiltMappings) 和 loader 生成 quilt 版本的 JSON 文件到磁盘中。
+///
+/// 复用 [`install_loader`]，因此和 `install_fabric` 一样支持库下载和安装进度上报。
+pub async fn install_quilt(
+    loader: FabricLoaderArtifact,
+    minecraft_location: MinecraftLocation,
+    options: QuiltInstallOptions,
+    update_sender: Option<Sender<InstallationUpdate>>,
+) -> String {
+    install_loader(
+        LoaderVariant::quilt(),
+        loader,
+        minecraft_location,
+        options.inherits_from,
+        options.version_id,
+        options.size,
+        options.yarn_version,
+        options.download_libraries,
+        options.mojang_meta_source,
+        update_sender,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test() {
+    let a = get_quilt_loader_artifact("1.19.4", "0.19.2").await;
+    let options = QuiltInstallOptions {
+        inherits_from: None,
+        version_id: None,
+        size: None,
+        yarn_version: None,
+        download_libraries: None,
+        mojang_meta_source: None,
+    };
+    let location = MinecraftLocation::new("test");
+    install_quilt(a, location, options, None).await;
+}